@@ -2,10 +2,14 @@
 
 use std::time::Duration;
 
-use matrix_sdk_common::deserialized_responses::ShieldState as RustShieldState;
+use matrix_sdk_common::deserialized_responses::{
+    EncryptionInfo as RustEncryptionInfo, ShieldState as RustShieldState,
+    VerificationLevel as RustVerificationLevel, VerificationState as RustVerificationState,
+};
+use ruma::OwnedDeviceId;
 use wasm_bindgen::prelude::*;
 
-use crate::events;
+use crate::{events, impl_from_to_inner};
 
 /// Settings for an encrypted room.
 ///
@@ -58,6 +62,40 @@ impl EncryptionSettings {
     pub fn new() -> EncryptionSettings {
         Self::default()
     }
+
+    /// Use an identity-based sharing strategy for this room, falling back to
+    /// a device-based strategy for users who have not published a
+    /// cross-signing identity.
+    ///
+    /// This lets a room get the stronger guarantees of identity-based
+    /// sharing without simply dropping room keys for users of legacy clients
+    /// that haven't set up cross-signing, addressing the gap documented on
+    /// {@link CollectStrategy.identityBasedStrategy}.
+    ///
+    /// * `device_fallback` - whether users without a published identity
+    ///   should fall back to the device-based, only-trusted-devices
+    ///   strategy, rather than being excluded entirely.
+    /// * `manually_trusted_devices` - device IDs that should be treated as
+    ///   trusted for the purposes of the device-based fallback, even if they
+    ///   haven't been interactively verified.
+    /// * `error_on_verified_user_problem` - as on {@link
+    ///   CollectStrategy.identityBasedStrategy}, abort key sharing rather
+    ///   than silently withholding the key when a recipient has a
+    ///   verification violation.
+    #[wasm_bindgen(js_name = "withIdentityPreferredSharing")]
+    pub fn with_identity_preferred_sharing(
+        mut self,
+        device_fallback: bool,
+        manually_trusted_devices: Option<Vec<String>>,
+        error_on_verified_user_problem: bool,
+    ) -> EncryptionSettings {
+        self.sharing_strategy = CollectStrategy::identity_preferred_strategy(
+            device_fallback,
+            manually_trusted_devices,
+            error_on_verified_user_problem,
+        );
+        self
+    }
 }
 
 impl From<&EncryptionSettings> for matrix_sdk_crypto::olm::EncryptionSettings {
@@ -118,51 +156,249 @@ impl From<matrix_sdk_crypto::types::EventEncryptionAlgorithm> for EncryptionAlgo
 
 /// Strategy to collect the devices that should receive room keys for the
 /// current discussion.
-#[wasm_bindgen()]
+///
+/// Use the static constructors below to build one, since the underlying
+/// strategies carry extra configuration that doesn't fit in a plain
+/// fieldless enum.
+#[wasm_bindgen]
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum CollectStrategy {
+pub struct CollectStrategy {
+    inner: matrix_sdk_crypto::CollectStrategy,
+}
+
+#[wasm_bindgen]
+impl CollectStrategy {
     /// Device based sharing strategy, excluding devices that are not trusted.
+    ///
     /// A device is trusted if any of the following is true:
     ///     - It was manually marked as trusted.
     ///     - It was marked as verified via interactive verification.
     ///     - It is signed by its owner identity, and this identity has been
     ///       trusted via interactive verification.
     ///     - It is the current own device of the user.
-    DeviceBasedStrategyOnlyTrustedDevices,
+    ///
+    /// In order to share a room key, a verified user (i.e. a user whose
+    /// identity we verified) must not have any unverified or unsigned
+    /// devices. If a verified user has an unsigned device, key sharing will
+    /// fail with a `VerifiedUserHasUnsignedDevice` error. If the verified
+    /// user has an unverified device, key sharing will fail with a
+    /// `VerifiedUserChangedIdentity` error.
+    ///
+    /// This behaviour can be disabled by setting `errorOnVerifiedUserProblem`
+    /// to `false`, in which case unsigned or unverified devices of a verified
+    /// user will simply be excluded from the key sharing instead of aborting
+    /// it.
+    #[wasm_bindgen(js_name = "deviceBasedStrategyOnlyTrustedDevices")]
+    pub fn device_based_strategy_only_trusted_devices(
+        error_on_verified_user_problem: bool,
+    ) -> CollectStrategy {
+        Self {
+            inner: matrix_sdk_crypto::CollectStrategy::DeviceBasedStrategy {
+                only_allow_trusted_devices: true,
+                error_on_verified_user_problem,
+            },
+        }
+    }
+
     /// Device based sharing strategy, including all devices.
-    DeviceBasedStrategyAllDevices,
+    #[wasm_bindgen(js_name = "deviceBasedStrategyAllDevices")]
+    pub fn device_based_strategy_all_devices() -> CollectStrategy {
+        Self {
+            inner: matrix_sdk_crypto::CollectStrategy::DeviceBasedStrategy {
+                only_allow_trusted_devices: false,
+                error_on_verified_user_problem: false,
+            },
+        }
+    }
+
     /// Share based on identity. Only distribute to devices signed by their
     /// owner. If a user has no published identity he will not receive
     /// any room keys.
-    IdentityBasedStrategy,
+    ///
+    /// If `errorOnVerifiedUserProblem` is set, key sharing will abort with an
+    /// error when a previously-verified user's identity has changed (see
+    /// {@link UserIdentity.hasVerificationViolation}), or when an unverified
+    /// identity was pinned under a previously-verified user, rather than
+    /// silently withholding the key from that user.
+    #[wasm_bindgen(js_name = "identityBasedStrategy")]
+    pub fn identity_based_strategy(error_on_verified_user_problem: bool) -> CollectStrategy {
+        Self {
+            inner: matrix_sdk_crypto::CollectStrategy::IdentityBasedStrategy {
+                error_on_verified_user_problem,
+            },
+        }
+    }
+
+    /// Share based on identity, falling back to the only-trusted-devices
+    /// strategy for users that have not published a cross-signing identity.
+    ///
+    /// This addresses the gap documented on {@link identityBasedStrategy}: a
+    /// user without a published identity will still receive the room key,
+    /// provided their individual devices are trusted, instead of being
+    /// silently excluded.
+    ///
+    /// * `device_fallback` - whether to fall back to the device-based,
+    ///   only-trusted-devices strategy for users with no published identity.
+    ///   If `false`, this behaves like {@link identityBasedStrategy}.
+    /// * `manually_trusted_devices` - device IDs that should be treated as
+    ///   trusted for the purposes of the device-based fallback, even if they
+    ///   haven't been interactively verified.
+    /// * `error_on_verified_user_problem` - same flag as on {@link
+    ///   identityBasedStrategy} and {@link
+    ///   deviceBasedStrategyOnlyTrustedDevices}: abort key sharing rather
+    ///   than silently withholding the key from a recipient whose identity
+    ///   has a verification violation, or who is pinned under a
+    ///   previously-verified user without being verified themselves.
+    #[wasm_bindgen(js_name = "identityPreferredStrategy")]
+    pub fn identity_preferred_strategy(
+        device_fallback: bool,
+        manually_trusted_devices: Option<Vec<String>>,
+        error_on_verified_user_problem: bool,
+    ) -> CollectStrategy {
+        Self {
+            inner: matrix_sdk_crypto::CollectStrategy::IdentityBasedStrategyWithDeviceFallback {
+                device_fallback,
+                manually_trusted_devices: manually_trusted_devices
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(OwnedDeviceId::from)
+                    .collect(),
+                error_on_verified_user_problem,
+            },
+        }
+    }
 }
 
 impl From<CollectStrategy> for matrix_sdk_crypto::CollectStrategy {
     fn from(value: CollectStrategy) -> Self {
-        match value {
-            CollectStrategy::DeviceBasedStrategyOnlyTrustedDevices => {
-                Self::DeviceBasedStrategy { only_allow_trusted_devices: true }
-            }
-            CollectStrategy::DeviceBasedStrategyAllDevices => {
-                Self::DeviceBasedStrategy { only_allow_trusted_devices: false }
-            }
-            CollectStrategy::IdentityBasedStrategy => Self::IdentityBasedStrategy,
-        }
+        value.inner
     }
 }
 
 impl From<matrix_sdk_crypto::CollectStrategy> for CollectStrategy {
     fn from(value: matrix_sdk_crypto::CollectStrategy) -> Self {
+        Self { inner: value }
+    }
+}
+
+/// Information on the encryption of a decrypted event, carrying the
+/// {@link VerificationState} of the device and user that sent it.
+///
+/// This crate does not yet expose a wasm type for a decrypted room event or
+/// timeline item, so there is currently no accessor anywhere that returns an
+/// `EncryptionInfo` to JS; it is a building block for such a type, to be
+/// wired up once that event type is added to these bindings.
+///
+/// Take a look at
+/// [`matrix_sdk_common::deserialized_responses::EncryptionInfo`] for more
+/// info.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct EncryptionInfo {
+    inner: RustEncryptionInfo,
+}
+
+impl_from_to_inner!(RustEncryptionInfo => EncryptionInfo);
+
+#[wasm_bindgen]
+impl EncryptionInfo {
+    /// The verification state of the device and user that sent the event at
+    /// the time it was decrypted.
+    #[wasm_bindgen(getter, js_name = "verificationState")]
+    pub fn verification_state(&self) -> VerificationState {
+        self.inner.verification_state.clone().into()
+    }
+}
+
+/// Represents the state of verification for a user or device, computed by
+/// taking into account the verification of the relevant cross-signing
+/// identities as well as any verification violations.
+///
+/// Take a look at
+/// [`matrix_sdk_common::deserialized_responses::VerificationState`] for more
+/// info.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct VerificationState {
+    inner: RustVerificationState,
+}
+
+impl_from_to_inner!(RustVerificationState => VerificationState);
+
+#[wasm_bindgen]
+impl VerificationState {
+    /// Is the sender of the event considered to be verified?
+    ///
+    /// If this is `false`, the {@link verificationLevel} will give the
+    /// reason why the sender is not considered verified.
+    #[wasm_bindgen(js_name = "isVerified")]
+    pub fn is_verified(&self) -> bool {
+        matches!(self.inner, RustVerificationState::Verified)
+    }
+
+    /// If the sender is not verified, the reason why. Returns
+    /// `VerificationLevel.None` if {@link isVerified} is `true`.
+    #[wasm_bindgen(getter, js_name = "verificationLevel")]
+    pub fn verification_level(&self) -> VerificationLevel {
+        match &self.inner {
+            RustVerificationState::Verified => VerificationLevel::None,
+            RustVerificationState::Unverified(level) => level.clone().into(),
+        }
+    }
+
+    /// Convert this verification state into a {@link ShieldState} using the
+    /// strict mode.
+    ///
+    /// In the strict mode, a signed device of an unverified user will be
+    /// reported as a grey warning, in addition to the other violations that
+    /// {@link toShieldStateLax} already reports.
+    #[wasm_bindgen(js_name = "toShieldStateStrict")]
+    pub fn to_shield_state_strict(&self) -> ShieldState {
+        self.inner.to_shield_state_strict().into()
+    }
+
+    /// Convert this verification state into a {@link ShieldState} using the
+    /// lax mode.
+    ///
+    /// In the lax mode, a signed device of an unverified user will not be
+    /// reported as a warning.
+    #[wasm_bindgen(js_name = "toShieldStateLax")]
+    pub fn to_shield_state_lax(&self) -> ShieldState {
+        self.inner.to_shield_state_lax().into()
+    }
+}
+
+/// The reason why a {@link VerificationState} is not considered to be fully
+/// verified.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationLevel {
+    /// The user that sent the event is identified, but their cross-signing
+    /// identity was not explicitly verified.
+    UnverifiedIdentity,
+
+    /// The user that sent the event is verified, but the device that sent
+    /// the event was not signed by their identity.
+    UnsignedDevice,
+
+    /// We previously verified this user, but their cross-signing identity has
+    /// now changed, or we have forgotten that the device was previously
+    /// verified, and they have not been re-verified since.
+    VerificationViolation,
+
+    /// No more specific reason is given. This is returned when the
+    /// corresponding {@link VerificationState} is verified, and also when it
+    /// is unverified with the underlying level `None`.
+    None,
+}
+
+impl From<RustVerificationLevel> for VerificationLevel {
+    fn from(value: RustVerificationLevel) -> Self {
         match value {
-            matrix_sdk_crypto::CollectStrategy::DeviceBasedStrategy {
-                only_allow_trusted_devices: true,
-            } => Self::DeviceBasedStrategyOnlyTrustedDevices,
-            matrix_sdk_crypto::CollectStrategy::DeviceBasedStrategy {
-                only_allow_trusted_devices: false,
-            } => Self::DeviceBasedStrategyAllDevices,
-            matrix_sdk_crypto::CollectStrategy::IdentityBasedStrategy => {
-                Self::IdentityBasedStrategy
-            }
+            RustVerificationLevel::UnverifiedIdentity => Self::UnverifiedIdentity,
+            RustVerificationLevel::UnsignedDevice => Self::UnsignedDevice,
+            RustVerificationLevel::VerificationViolation => Self::VerificationViolation,
+            RustVerificationLevel::None => Self::None,
         }
     }
 }
@@ -215,9 +451,12 @@ impl From<RustShieldState> for ShieldState {
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use matrix_sdk_common::deserialized_responses::{
+        VerificationLevel as RustVerificationLevel, VerificationState as RustVerificationState,
+    };
     use wasm_bindgen_test::wasm_bindgen_test;
 
-    use super::EncryptionAlgorithm;
+    use super::{EncryptionAlgorithm, ShieldColor, VerificationLevel, VerificationState};
 
     #[wasm_bindgen_test]
     fn test_convert_encryption_algorithm_to_js() {
@@ -237,4 +476,45 @@ pub(crate) mod tests {
             )) == EncryptionAlgorithm::Unknown
         );
     }
+
+    #[wasm_bindgen_test]
+    fn test_convert_verification_level_to_js() {
+        assert_eq!(
+            VerificationLevel::from(RustVerificationLevel::UnverifiedIdentity),
+            VerificationLevel::UnverifiedIdentity
+        );
+        assert_eq!(
+            VerificationLevel::from(RustVerificationLevel::UnsignedDevice),
+            VerificationLevel::UnsignedDevice
+        );
+        assert_eq!(
+            VerificationLevel::from(RustVerificationLevel::VerificationViolation),
+            VerificationLevel::VerificationViolation
+        );
+        assert_eq!(
+            VerificationLevel::from(RustVerificationLevel::None),
+            VerificationLevel::None
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verification_state_to_shield_state() {
+        let verified = VerificationState::from(RustVerificationState::Verified);
+        assert!(matches!(verified.to_shield_state_strict().color, ShieldColor::None));
+        assert!(matches!(verified.to_shield_state_lax().color, ShieldColor::None));
+
+        // A signed device of an unverified user is only a warning in strict mode.
+        let unverified_identity = VerificationState::from(RustVerificationState::Unverified(
+            RustVerificationLevel::UnverifiedIdentity,
+        ));
+        assert!(matches!(unverified_identity.to_shield_state_strict().color, ShieldColor::Grey));
+        assert!(matches!(unverified_identity.to_shield_state_lax().color, ShieldColor::None));
+
+        // A verification violation is always a red warning, in both modes.
+        let violation = VerificationState::from(RustVerificationState::Unverified(
+            RustVerificationLevel::VerificationViolation,
+        ));
+        assert!(matches!(violation.to_shield_state_strict().color, ShieldColor::Red));
+        assert!(matches!(violation.to_shield_state_lax().color, ShieldColor::Red));
+    }
 }
@@ -0,0 +1,199 @@
+//! Types for interactive (SAS and QR code) verification.
+
+use js_sys::Promise;
+use matrix_sdk_crypto::verification;
+use wasm_bindgen::prelude::*;
+
+use crate::{future::future_to_promise, identifiers, impl_from_to_inner, requests};
+
+/// A verification method that can be advertised in a verification request.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub enum VerificationMethod {
+    /// The `m.sas.v1` verification method.
+    SasV1,
+
+    /// The `m.qr_code.scan.v1` verification method.
+    QrCodeScanV1,
+
+    /// The `m.qr_code.show.v1` verification method.
+    QrCodeShowV1,
+
+    /// The `m.reciprocate.v1` verification method.
+    ReciprocateV1,
+}
+
+impl From<VerificationMethod> for verification::VerificationMethod {
+    fn from(value: VerificationMethod) -> Self {
+        match value {
+            VerificationMethod::SasV1 => Self::SasV1,
+            VerificationMethod::QrCodeScanV1 => Self::QrCodeScanV1,
+            VerificationMethod::QrCodeShowV1 => Self::QrCodeShowV1,
+            VerificationMethod::ReciprocateV1 => Self::ReciprocateV1,
+        }
+    }
+}
+
+impl From<&VerificationMethod> for verification::VerificationMethod {
+    fn from(value: &VerificationMethod) -> Self {
+        (*value).into()
+    }
+}
+
+/// An outgoing request that must be sent out by the application as a
+/// consequence of driving a verification flow forward.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct OutgoingVerificationRequest {
+    inner: matrix_sdk_crypto::OutgoingVerificationRequest,
+}
+
+impl_from_to_inner!(matrix_sdk_crypto::OutgoingVerificationRequest => OutgoingVerificationRequest);
+
+impl TryFrom<OutgoingVerificationRequest> for JsValue {
+    type Error = JsError;
+
+    fn try_from(value: OutgoingVerificationRequest) -> Result<Self, Self::Error> {
+        Ok(requests::OutgoingRequest::try_from(&value.inner.into())?.into())
+    }
+}
+
+/// An object controlling the verification flow of a 1-to-1 verification
+/// (a verification that isn't tied to a specific room).
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct VerificationRequest {
+    inner: verification::VerificationRequest,
+}
+
+impl_from_to_inner!(verification::VerificationRequest => VerificationRequest);
+
+#[wasm_bindgen]
+impl VerificationRequest {
+    /// Has the verification flow that was started with this request been
+    /// cancelled?
+    #[wasm_bindgen(js_name = "isCancelled")]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    /// Has the verification flow been successfully completed?
+    #[wasm_bindgen(js_name = "isDone")]
+    pub fn is_done(&self) -> bool {
+        self.inner.is_done()
+    }
+
+    /// Generate a QR code that can be shown to the other device/user so they
+    /// can scan it and reciprocate the verification.
+    ///
+    /// Resolves to `undefined` if the current verification request doesn't
+    /// support showing a QR code, for example if the other side hasn't yet
+    /// accepted the `m.qr_code.show.v1` method.
+    #[wasm_bindgen(js_name = "generateQrCode")]
+    pub fn generate_qr_code(&self) -> Promise {
+        let me = self.inner.clone();
+
+        future_to_promise(async move {
+            Ok(match me.generate_qr_code().await? {
+                Some(qr_verification) => JsValue::from(QrVerification::from(qr_verification)),
+                None => JsValue::undefined(),
+            })
+        })
+    }
+
+    /// Scan the data of a QR code that was shown by the other device/user and
+    /// use it to start a QR code verification.
+    ///
+    /// The `data` is the raw byte buffer that was encoded in the QR code,
+    /// as produced by {@link QrVerification.toBytes} on the other side.
+    #[wasm_bindgen(js_name = "scanQrCode")]
+    pub fn scan_qr_code(&self, data: Vec<u8>) -> Promise {
+        let me = self.inner.clone();
+
+        future_to_promise(async move {
+            let data = verification::QrVerificationData::from_bytes(data)?;
+            Ok(QrVerification::from(me.scan_qr_code(data).await?))
+        })
+    }
+}
+
+/// An object controlling a QR code verification flow.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct QrVerification {
+    inner: verification::QrVerification,
+}
+
+impl_from_to_inner!(verification::QrVerification => QrVerification);
+
+#[wasm_bindgen]
+impl QrVerification {
+    /// The user ID of the other user that is participating in this
+    /// verification flow.
+    #[wasm_bindgen(getter, js_name = "otherUserId")]
+    pub fn other_user_id(&self) -> identifiers::UserId {
+        self.inner.other_user_id().to_owned().into()
+    }
+
+    /// The bytes that should be encoded as a QR code and displayed to the
+    /// other side, in the `matrix-qrcode` wire format (the `MATRIX` prefix,
+    /// a version byte, a mode byte, the flow ID, two keys and a shared
+    /// secret).
+    #[wasm_bindgen(js_name = "toBytes")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.inner.to_bytes()?)
+    }
+
+    /// Has the QR code verification been cancelled, either by us or by the
+    /// other side?
+    #[wasm_bindgen(js_name = "isCancelled")]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    /// Has the other side reciprocated the scan, i.e. scanned our QR code
+    /// and sent back the shared secret it contains?
+    #[wasm_bindgen(js_name = "reciprocated")]
+    pub fn reciprocated(&self) -> bool {
+        self.inner.reciprocated()
+    }
+
+    /// Has this side confirmed the reciprocated scan, completing the QR code
+    /// verification?
+    #[wasm_bindgen(js_name = "hasBeenConfirmed")]
+    pub fn has_been_confirmed(&self) -> bool {
+        self.inner.has_been_confirmed()
+    }
+
+    /// Confirm that the scan performed by the other side was indeed
+    /// successful, marking the other device/identity as verified and sending
+    /// out the `m.key.verification.done` event.
+    ///
+    /// This should only be called after {@link reciprocated} has become
+    /// `true`.
+    pub fn confirm(&self) -> Promise {
+        let me = self.inner.clone();
+
+        future_to_promise(async move {
+            let outgoing_requests = me.confirm().await?;
+
+            Ok(outgoing_requests
+                .into_iter()
+                .map(OutgoingVerificationRequest::from)
+                .map(JsValue::try_from)
+                .collect::<Result<js_sys::Array, _>>()?)
+        })
+    }
+
+    /// Cancel the verification flow.
+    pub fn cancel(&self) -> Promise {
+        let me = self.inner.clone();
+
+        future_to_promise(async move {
+            Ok(match me.cancel().await {
+                Some(request) => JsValue::try_from(OutgoingVerificationRequest::from(request))?,
+                None => JsValue::undefined(),
+            })
+        })
+    }
+}